@@ -0,0 +1,107 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+use conv3d::{
+    convert_stl_to_gltf_with_color, convert_stl_to_obj_with_options, FileFormat, PbrMaterial,
+};
+
+/// Converts an STL mesh to glTF, GLB, or OBJ.
+#[derive(Parser)]
+#[command(author, version, about)]
+struct Cli {
+    /// Input STL file.
+    input: PathBuf,
+
+    /// Output file; its extension picks the target format unless `--format` is given.
+    output: PathBuf,
+
+    /// Output format, inferred from `output`'s extension if omitted.
+    #[arg(long, value_enum)]
+    format: Option<FileFormat>,
+
+    /// Solid base color ("r,g,b", each 0.0-1.0) to assign to the converted mesh,
+    /// so it doesn't render as flat untextured gray.
+    #[arg(long, value_parser = parse_color)]
+    color: Option<[f32; 3]>,
+}
+
+fn parse_color(s: &str) -> Result<[f32; 3], String> {
+    let parts: Vec<&str> = s.split(',').collect();
+    let [r, g, b] = parts.as_slice() else {
+        return Err(format!("expected \"r,g,b\", got {s:?}"));
+    };
+    let parse = |p: &str| {
+        p.trim()
+            .parse::<f32>()
+            .map_err(|e| format!("invalid color component {p:?}: {e}"))
+    };
+    Ok([parse(r)?, parse(g)?, parse(b)?])
+}
+
+fn format_for(cli: &Cli) -> FileFormat {
+    cli.format
+        .clone()
+        .unwrap_or_else(|| match cli.output.extension().and_then(|e| e.to_str()) {
+            Some("glb") => FileFormat::Glb,
+            Some("obj") => FileFormat::Obj,
+            _ => FileFormat::Gltf,
+        })
+}
+
+fn main() -> Result<(), String> {
+    let cli = Cli::parse();
+
+    let mut file = File::open(&cli.input)
+        .map_err(|e| format!("failed to open {}: {e}", cli.input.display()))?;
+    let stl = stl_io::read_stl(&mut file)
+        .map_err(|e| format!("failed to parse {}: {e}", cli.input.display()))?;
+    let color = cli.color.map(PbrMaterial::solid_color);
+
+    match format_for(&cli) {
+        FileFormat::Obj => {
+            let doc = convert_stl_to_obj_with_options(stl, cli.color, None, None)?;
+
+            // Only reference (and write) a companion .mtl when there's an
+            // actual material to put in it.
+            let mtl_name = doc.material.is_some().then(|| {
+                format!(
+                    "{}.mtl",
+                    cli.output.file_stem().unwrap_or_default().to_string_lossy()
+                )
+            });
+
+            let out = File::create(&cli.output).map_err(|e| e.to_string())?;
+            doc.write_obj(BufWriter::new(out), mtl_name.as_deref())?;
+
+            if let Some(mtl_name) = &mtl_name {
+                let mtl_path = cli.output.parent().unwrap_or(Path::new(".")).join(mtl_name);
+                let mtl_file = File::create(&mtl_path).map_err(|e| e.to_string())?;
+                doc.write_mtl(BufWriter::new(mtl_file))?;
+            }
+        }
+        FileFormat::Glb => {
+            let gltf = convert_stl_to_gltf_with_color(stl, &cli.input, color)?;
+            let out = File::create(&cli.output).map_err(|e| e.to_string())?;
+            let glb = gltf.to_glb()?;
+            glb.to_writer(BufWriter::new(out))
+                .map_err(|e| e.to_string())?;
+        }
+        FileFormat::Gltf => {
+            let gltf = convert_stl_to_gltf_with_color(stl, &cli.input, color)?;
+            let mut gltf = gltf.merge_gltf_buffers()?;
+            let bin_name = format!(
+                "{}.bin",
+                cli.output.file_stem().unwrap_or_default().to_string_lossy()
+            );
+            gltf.set_buffer_uri(0, Some(bin_name))?;
+            let out = File::create(&cli.output).map_err(|e| e.to_string())?;
+            gltf.write_to_gltf(BufWriter::new(out))?;
+            gltf.write_all_buffers(cli.output.parent().unwrap_or(Path::new(".")))?;
+        }
+        FileFormat::Stl => return Err("STL is only supported as an input format here".to_string()),
+    }
+
+    Ok(())
+}
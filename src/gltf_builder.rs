@@ -0,0 +1,459 @@
+use gltf::binary::Glb;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use crate::json;
+use crate::Valid;
+
+/// Raw little-endian byte packing for the handful of buffer element types
+/// the builder round-trips through glTF buffer views.
+trait BufferBytes {
+    fn byte_len(&self) -> usize;
+    fn push_bytes(&self, out: &mut Vec<u8>);
+}
+
+impl BufferBytes for [f32; 3] {
+    fn byte_len(&self) -> usize {
+        12
+    }
+
+    fn push_bytes(&self, out: &mut Vec<u8>) {
+        for c in self {
+            out.extend_from_slice(&c.to_le_bytes());
+        }
+    }
+}
+
+impl BufferBytes for u32 {
+    fn byte_len(&self) -> usize {
+        4
+    }
+
+    fn push_bytes(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+}
+
+/// A position/normal pair packed contiguously, for the interleaved
+/// [`VertexLayout`] GPU loaders prefer for cache locality.
+pub struct InterleavedVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+}
+
+impl BufferBytes for InterleavedVertex {
+    fn byte_len(&self) -> usize {
+        24
+    }
+
+    fn push_bytes(&self, out: &mut Vec<u8>) {
+        self.position.push_bytes(out);
+        self.normal.push_bytes(out);
+    }
+}
+
+/// How a mesh's position/normal attributes are laid out across buffer views.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VertexLayout {
+    /// One buffer view per attribute (the original layout).
+    #[default]
+    Planar,
+    /// Positions and normals interleaved into one buffer view with a
+    /// `byteStride` of 24 bytes, at offsets 0 and 12 respectively.
+    Interleaved,
+}
+
+fn pack<T: BufferBytes>(data: &[T]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(data.iter().map(BufferBytes::byte_len).sum());
+    for item in data {
+        item.push_bytes(&mut bytes);
+    }
+    bytes
+}
+
+fn align_to_four(bytes: &mut Vec<u8>) {
+    while bytes.len() % 4 != 0 {
+        bytes.push(0);
+    }
+}
+
+/// A node's local translation/rotation/scale. Each component is
+/// independently optional, mirroring glTF's `node.translation/rotation/scale`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NodeTrs {
+    pub translation: Option<[f32; 3]>,
+    pub rotation: Option<[f32; 4]>,
+    pub scale: Option<[f32; 3]>,
+}
+
+/// Parameters for a PBR metallic-roughness material, as attached to a
+/// primitive via [`GltfBuilder::push_material`].
+#[derive(Debug, Clone, Copy)]
+pub struct PbrMaterial {
+    pub base_color_factor: [f32; 4],
+    pub metallic_factor: f32,
+    pub roughness_factor: f32,
+    pub emissive_factor: Option<[f32; 3]>,
+    pub double_sided: bool,
+}
+
+impl Default for PbrMaterial {
+    fn default() -> Self {
+        PbrMaterial {
+            base_color_factor: [0.8, 0.8, 0.8, 1.0],
+            metallic_factor: 0.0,
+            roughness_factor: 0.8,
+            emissive_factor: None,
+            double_sided: false,
+        }
+    }
+}
+
+impl PbrMaterial {
+    /// A solid, fully opaque base color with otherwise-default PBR factors.
+    pub fn solid_color(rgb: [f32; 3]) -> Self {
+        PbrMaterial {
+            base_color_factor: [rgb[0], rgb[1], rgb[2], 1.0],
+            ..Default::default()
+        }
+    }
+}
+
+/// Incrementally assembles a glTF [`json::Root`] plus the raw bytes backing
+/// each buffer view, so callers can push attributes/meshes/nodes one at a
+/// time without hand-rolling index bookkeeping.
+pub struct GltfBuilder {
+    root: json::Root,
+    buffers: Vec<Vec<u8>>,
+}
+
+impl Default for GltfBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GltfBuilder {
+    pub fn new() -> Self {
+        GltfBuilder {
+            root: json::Root::default(),
+            buffers: Vec::new(),
+        }
+    }
+
+    /// Packs `data` into its own buffer + buffer view, returning the view's
+    /// index. `target` is the `ARRAY_BUFFER`/`ELEMENT_ARRAY_BUFFER` binding
+    /// hint, `stride` an explicit `byteStride` for interleaved data.
+    pub fn push_buffer_with_view<T: BufferBytes>(
+        &mut self,
+        name: Option<String>,
+        data: Vec<T>,
+        target: Option<json::buffer::Target>,
+        stride: Option<usize>,
+    ) -> json::Index<json::buffer::View> {
+        let mut bytes = pack(&data);
+        align_to_four(&mut bytes);
+        let byte_length = bytes.len() as u32;
+
+        let buffer = self.root.push(json::Buffer {
+            byte_length: byte_length.into(),
+            extensions: Default::default(),
+            extras: Default::default(),
+            name: name.clone(),
+            uri: None,
+        });
+
+        let view = self.root.push(json::buffer::View {
+            buffer,
+            byte_length: byte_length.into(),
+            byte_offset: None,
+            byte_stride: stride.map(json::buffer::Stride),
+            extensions: Default::default(),
+            extras: Default::default(),
+            name,
+            target: target.map(Valid),
+        });
+
+        self.buffers.push(bytes);
+        view
+    }
+
+    /// Packs `positions`/`normals` into one interleaved buffer view (stride
+    /// 24: a `[f32; 3]` position immediately followed by a `[f32; 3]`
+    /// normal), as GPU loaders prefer for cache locality. Returns the shared
+    /// view; pass byte offsets 0 and 12 into it to
+    /// [`Self::push_accessor_vec3`] for positions and normals respectively.
+    pub fn push_interleaved_view(
+        &mut self,
+        name: Option<String>,
+        positions: Vec<[f32; 3]>,
+        normals: Vec<[f32; 3]>,
+    ) -> json::Index<json::buffer::View> {
+        let vertices = positions
+            .into_iter()
+            .zip(normals)
+            .map(|(position, normal)| InterleavedVertex { position, normal })
+            .collect::<Vec<_>>();
+
+        self.push_buffer_with_view(
+            name,
+            vertices,
+            Some(json::buffer::Target::ArrayBuffer),
+            Some(24),
+        )
+    }
+
+    pub fn push_accessor_vec3(
+        &mut self,
+        name: Option<String>,
+        view: json::Index<json::buffer::View>,
+        byte_offset: usize,
+        count: usize,
+        min: Option<[f32; 3]>,
+        max: Option<[f32; 3]>,
+    ) -> json::Index<json::Accessor> {
+        self.root.push(json::Accessor {
+            buffer_view: Some(view),
+            byte_offset: Some((byte_offset as u32).into()),
+            count: (count as u32).into(),
+            component_type: Valid(json::accessor::GenericComponentType(
+                json::accessor::ComponentType::F32,
+            )),
+            extensions: Default::default(),
+            extras: Default::default(),
+            type_: Valid(json::accessor::Type::Vec3),
+            min: min.map(|m| json::serialize::to_value(Vec::from(m)).unwrap()),
+            max: max.map(|m| json::serialize::to_value(Vec::from(m)).unwrap()),
+            name,
+            normalized: false,
+            sparse: None,
+        })
+    }
+
+    pub fn push_accessor_u32(
+        &mut self,
+        name: Option<String>,
+        view: json::Index<json::buffer::View>,
+        byte_offset: usize,
+        count: usize,
+    ) -> json::Index<json::Accessor> {
+        self.root.push(json::Accessor {
+            buffer_view: Some(view),
+            byte_offset: Some((byte_offset as u32).into()),
+            count: (count as u32).into(),
+            component_type: Valid(json::accessor::GenericComponentType(
+                json::accessor::ComponentType::U32,
+            )),
+            extensions: Default::default(),
+            extras: Default::default(),
+            type_: Valid(json::accessor::Type::Scalar),
+            min: None,
+            max: None,
+            name,
+            normalized: false,
+            sparse: None,
+        })
+    }
+
+    /// Pushes a PBR metallic-roughness material, e.g. a solid base color so
+    /// converted models don't render as flat untextured gray.
+    pub fn push_material(
+        &mut self,
+        name: Option<String>,
+        material: PbrMaterial,
+    ) -> json::Index<json::material::Material> {
+        self.root.push(json::Material {
+            alpha_cutoff: None,
+            alpha_mode: Valid(json::material::AlphaMode::Opaque),
+            double_sided: material.double_sided,
+            emissive_factor: json::material::EmissiveFactor(
+                material.emissive_factor.unwrap_or([0.0; 3]),
+            ),
+            extensions: Default::default(),
+            extras: Default::default(),
+            name,
+            normal_texture: None,
+            occlusion_texture: None,
+            pbr_metallic_roughness: json::material::PbrMetallicRoughness {
+                base_color_factor: json::material::PbrBaseColorFactor(material.base_color_factor),
+                base_color_texture: None,
+                metallic_factor: json::material::StrengthFactor(material.metallic_factor),
+                roughness_factor: json::material::StrengthFactor(material.roughness_factor),
+                metallic_roughness_texture: None,
+                extensions: Default::default(),
+                extras: Default::default(),
+            },
+        })
+    }
+
+    pub fn push_mesh(
+        &mut self,
+        name: Option<String>,
+        primitives: Vec<json::mesh::Primitive>,
+        weights: Option<Vec<f32>>,
+    ) -> json::Index<json::Mesh> {
+        self.root.push(json::Mesh {
+            extensions: Default::default(),
+            extras: Default::default(),
+            name,
+            primitives,
+            weights,
+        })
+    }
+
+    pub fn push_node(
+        &mut self,
+        name: Option<String>,
+        mesh: Option<json::Index<json::Mesh>>,
+        translation: Option<[f32; 3]>,
+        children: Option<Vec<json::Index<json::scene::Node>>>,
+    ) -> json::Index<json::scene::Node> {
+        self.push_node_trs(
+            name,
+            mesh,
+            NodeTrs {
+                translation,
+                ..Default::default()
+            },
+            children,
+        )
+    }
+
+    /// Like [`Self::push_node`], but carries the full TRS (translation,
+    /// rotation, scale) a node can have, e.g. when laying out an assembly
+    /// of several meshes in one scene.
+    pub fn push_node_trs(
+        &mut self,
+        name: Option<String>,
+        mesh: Option<json::Index<json::Mesh>>,
+        trs: NodeTrs,
+        children: Option<Vec<json::Index<json::scene::Node>>>,
+    ) -> json::Index<json::scene::Node> {
+        self.root.push(json::scene::Node {
+            camera: None,
+            children,
+            extensions: Default::default(),
+            extras: Default::default(),
+            matrix: None,
+            mesh,
+            name,
+            rotation: trs.rotation.map(json::scene::UnitQuaternion),
+            scale: trs.scale,
+            translation: trs.translation,
+            skin: None,
+            weights: None,
+        })
+    }
+
+    pub fn push_scene(
+        &mut self,
+        nodes: Vec<json::Index<json::scene::Node>>,
+    ) -> json::Index<json::Scene> {
+        self.root.push(json::Scene {
+            extensions: Default::default(),
+            extras: Default::default(),
+            name: None,
+            nodes,
+        })
+    }
+
+    pub fn set_default_scene(&mut self, scene: Option<json::Index<json::Scene>>) {
+        self.root.scene = scene;
+    }
+
+    /// Concatenates every buffer's bytes into buffer 0, rewriting the
+    /// `byteOffset` of every view that pointed at a now-merged buffer.
+    pub fn merge_gltf_buffers(mut self) -> Result<Self, String> {
+        if self.buffers.len() <= 1 {
+            return Ok(self);
+        }
+
+        let mut merged = Vec::new();
+        let mut offsets = Vec::with_capacity(self.buffers.len());
+        for buffer in &self.buffers {
+            offsets.push(merged.len());
+            merged.extend_from_slice(buffer);
+        }
+
+        for view in self.root.buffer_views.iter_mut() {
+            let offset = offsets[view.buffer.value()];
+            view.byte_offset =
+                Some((offset as u32 + view.byte_offset.map_or(0, |o| o.0 as u32)).into());
+            view.buffer = json::Index::new(0);
+        }
+
+        self.root.buffers = vec![json::Buffer {
+            byte_length: (merged.len() as u32).into(),
+            extensions: Default::default(),
+            extras: Default::default(),
+            name: None,
+            uri: None,
+        }];
+        self.buffers = vec![merged];
+
+        Ok(self)
+    }
+
+    pub fn set_buffer_uri(&mut self, index: usize, uri: Option<String>) -> Result<(), String> {
+        let buffer = self
+            .root
+            .buffers
+            .get_mut(index)
+            .ok_or_else(|| format!("no buffer at index {index}"))?;
+        buffer.uri = uri;
+        Ok(())
+    }
+
+    pub fn write_to_gltf<W: Write>(&self, mut writer: W) -> Result<(), String> {
+        json::serialize::to_writer_pretty(&mut writer, &self.root).map_err(|e| e.to_string())
+    }
+
+    pub fn write_all_buffers(&self, dir: impl AsRef<Path>) -> Result<(), String> {
+        for (buffer, bytes) in self.root.buffers.iter().zip(&self.buffers) {
+            let Some(uri) = buffer.uri.as_ref() else {
+                continue;
+            };
+            let path = dir.as_ref().join(uri);
+            fs::write(&path, bytes)
+                .map_err(|e| format!("failed to write {}: {e}", path.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Merges every buffer view onto a single binary chunk and serializes the
+    /// result as a `.glb`. Unlike [`Self::merge_gltf_buffers`] this leaves
+    /// `self` untouched, since a GLB's merged buffer has no `uri` and can't
+    /// be written out as a loose `.bin` file the way a `.gltf` export can.
+    pub fn to_glb(&self) -> Result<Glb<'static>, String> {
+        let merged = GltfBuilder {
+            root: self.root.clone(),
+            buffers: self.buffers.clone(),
+        }
+        .merge_gltf_buffers()?;
+
+        let json_string = json::serialize::to_string(&merged.root).map_err(|e| e.to_string())?;
+        let mut json_bytes = json_string.into_bytes();
+        align_to_four(&mut json_bytes);
+
+        let bin = merged.buffers.into_iter().next().unwrap_or_default();
+
+        Ok(Glb {
+            header: gltf::binary::Header {
+                magic: *b"glTF",
+                version: 2,
+                length: (json_bytes.len() + bin.len()) as u32,
+            },
+            json: std::borrow::Cow::Owned(json_bytes),
+            bin: Some(std::borrow::Cow::Owned(bin)),
+        })
+    }
+
+    pub fn root(&self) -> &json::Root {
+        &self.root
+    }
+
+    pub fn root_mut(&mut self) -> &mut json::Root {
+        &mut self.root
+    }
+}
@@ -1,10 +1,14 @@
 pub mod gltf_builder;
 pub use gltf_builder::*;
 
+pub mod obj_writer;
+pub use obj_writer::*;
+
 pub use gltf::json;
 pub use json::validation::Checked::Valid;
 
 use clap::clap_derive::ValueEnum;
+use std::collections::HashMap;
 use std::path::Path;
 use stl_io::IndexedMesh;
 
@@ -13,6 +17,7 @@ pub enum FileFormat {
     Stl,
     Gltf,
     Glb,
+    Obj,
 }
 
 pub fn get_extension(format: FileFormat) -> &'static str {
@@ -20,6 +25,7 @@ pub fn get_extension(format: FileFormat) -> &'static str {
         FileFormat::Stl => "stl",
         FileFormat::Gltf => "gltf",
         FileFormat::Glb => "glb",
+        FileFormat::Obj => "obj",
     }
 }
 
@@ -37,88 +43,431 @@ pub fn bounding_coords(points: &[[f32; 3]]) -> ([f32; 3], [f32; 3]) {
     (min, max)
 }
 
-pub fn convert_stl_to_gltf(
-    stl: IndexedMesh,
-    input_filename: impl AsRef<Path>,
-) -> Result<GltfBuilder, String> {
-    let mesh_name = input_filename
-        .as_ref()
-        .file_stem()
-        .unwrap()
-        .to_string_lossy()
-        .to_string();
+/// Multiplies a 4x4 column-major matrix (as returned by `gltf::scene::Transform::matrix`)
+/// by a point, treating it as affine (`w = 1`).
+fn transform_point(matrix: &[[f32; 4]; 4], point: [f32; 3]) -> [f32; 3] {
+    let [x, y, z] = point;
+    let mut out = [0.0; 3];
+    for i in 0..3 {
+        out[i] = matrix[0][i] * x + matrix[1][i] * y + matrix[2][i] * z + matrix[3][i];
+    }
+    out
+}
 
-    let mut gltf = GltfBuilder::new();
-    let with_indices = true;
+fn matmul(a: &[[f32; 4]; 4], b: &[[f32; 4]; 4]) -> [[f32; 4]; 4] {
+    let mut out = [[0.0; 4]; 4];
+    for col in 0..4 {
+        for row in 0..4 {
+            out[col][row] = (0..4).map(|k| a[k][row] * b[col][k]).sum();
+        }
+    }
+    out
+}
+
+/// Reads every mesh primitive out of a glTF/GLB document's default scene
+/// (falling back to the first declared scene if the document doesn't name
+/// one), baking each node's world transform into its triangles, and
+/// flattens the result into a single `stl_io::IndexedMesh` suitable for 3D
+/// printing. A primitive in any mode other than `Triangles` is an error.
+pub fn convert_gltf_to_stl(path: impl AsRef<Path>) -> Result<IndexedMesh, String> {
+    let (document, buffers, _images) =
+        gltf::import(path.as_ref()).map_err(|e| format!("failed to import glTF: {e}"))?;
+
+    let mut vertices = Vec::new();
+    let mut faces = Vec::new();
+
+    let scene = document
+        .default_scene()
+        .or_else(|| document.scenes().next())
+        .ok_or("glTF document has no scenes")?;
+    for node in scene.nodes() {
+        walk_gltf_node(
+            &node,
+            &buffers,
+            identity_matrix(),
+            &mut vertices,
+            &mut faces,
+        )?;
+    }
+
+    Ok(IndexedMesh { vertices, faces })
+}
+
+fn identity_matrix() -> [[f32; 4]; 4] {
+    [
+        [1.0, 0.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ]
+}
+
+fn walk_gltf_node(
+    node: &gltf::Node,
+    buffers: &[gltf::buffer::Data],
+    parent_transform: [[f32; 4]; 4],
+    vertices: &mut Vec<stl_io::Vertex>,
+    faces: &mut Vec<stl_io::IndexedTriangle>,
+) -> Result<(), String> {
+    let world_transform = matmul(&parent_transform, &node.transform().matrix());
+
+    if let Some(mesh) = node.mesh() {
+        for primitive in mesh.primitives() {
+            if primitive.mode() != gltf::mesh::Mode::Triangles {
+                return Err(format!(
+                    "unsupported primitive mode {:?} on node {:?}: only Triangles is supported",
+                    primitive.mode(),
+                    node.name().unwrap_or("<unnamed>"),
+                ));
+            }
+
+            let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+
+            let Some(positions) = reader.read_positions() else {
+                continue;
+            };
+            let base_index = vertices.len();
+            for position in positions {
+                let world = transform_point(&world_transform, position);
+                vertices.push(stl_io::Vertex::new([world[0], world[1], world[2]]));
+            }
+
+            // A primitive without an index buffer draws its vertices in order.
+            let indices = match reader.read_indices() {
+                Some(indices) => indices.into_u32().collect::<Vec<_>>(),
+                None => (0..(vertices.len() - base_index) as u32).collect(),
+            };
+            for tri in indices.chunks_exact(3) {
+                let vi = [
+                    base_index + tri[0] as usize,
+                    base_index + tri[1] as usize,
+                    base_index + tri[2] as usize,
+                ];
+                let a = vertices[vi[0]];
+                let b = vertices[vi[1]];
+                let c = vertices[vi[2]];
+                let e1 = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+                let e2 = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+                let normal = normalize3([
+                    e1[1] * e2[2] - e1[2] * e2[1],
+                    e1[2] * e2[0] - e1[0] * e2[2],
+                    e1[0] * e2[1] - e1[1] * e2[0],
+                ]);
+                faces.push(stl_io::IndexedTriangle {
+                    normal: stl_io::Normal::new(normal),
+                    vertices: vi,
+                });
+            }
+        }
+    }
+
+    for child in node.children() {
+        walk_gltf_node(&child, buffers, world_transform, vertices, faces)?;
+    }
+
+    Ok(())
+}
+
+fn sub3(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn add3(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn scale3(a: [f32; 3], s: f32) -> [f32; 3] {
+    [a[0] * s, a[1] * s, a[2] * s]
+}
+
+fn dot3(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn normalize3(a: [f32; 3]) -> [f32; 3] {
+    let len = dot3(a, a).sqrt();
+    if len > f32::EPSILON {
+        scale3(a, 1.0 / len)
+    } else {
+        [0.0, 0.0, 0.0]
+    }
+}
+
+/// Welds vertices within `epsilon` of each other using a spatial hash grid,
+/// remapping face indices onto the deduplicated set. STL facet soup
+/// frequently has coincident vertices at face boundaries that bloat the
+/// output and break smooth shading; run this before [`compute_shaded_mesh`]
+/// to collapse them first.
+fn weld_vertices(stl: &IndexedMesh, epsilon: f32) -> IndexedMesh {
+    let cell_of = |p: [f32; 3]| -> (i64, i64, i64) {
+        (
+            (p[0] / epsilon).floor() as i64,
+            (p[1] / epsilon).floor() as i64,
+            (p[2] / epsilon).floor() as i64,
+        )
+    };
+
+    // Canonical vertex indices seen so far, keyed by grid cell; checking a
+    // vertex's own cell plus its 26 neighbors avoids splitting duplicates
+    // that happen to straddle a cell border.
+    let mut cells: HashMap<(i64, i64, i64), Vec<usize>> = HashMap::new();
+    let mut vertices: Vec<stl_io::Vertex> = Vec::new();
+    let mut remap = vec![0usize; stl.vertices.len()];
+
+    for (vi, &v) in stl.vertices.iter().enumerate() {
+        let p = [v[0], v[1], v[2]];
+        let (cx, cy, cz) = cell_of(p);
+
+        let mut existing = None;
+        'search: for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    let Some(candidates) = cells.get(&(cx + dx, cy + dy, cz + dz)) else {
+                        continue;
+                    };
+                    for &ci in candidates {
+                        let q = vertices[ci];
+                        let d = sub3(p, [q[0], q[1], q[2]]);
+                        if dot3(d, d) <= epsilon * epsilon {
+                            existing = Some(ci);
+                            break 'search;
+                        }
+                    }
+                }
+            }
+        }
+
+        let canonical = existing.unwrap_or_else(|| {
+            let ci = vertices.len();
+            vertices.push(v);
+            cells.entry((cx, cy, cz)).or_default().push(ci);
+            ci
+        });
+        remap[vi] = canonical;
+    }
 
-    let (positions, mut normals) = stl
-        .vertices
+    let faces = stl
+        .faces
         .iter()
-        .map(|it| ([it[0], it[1], it[2]], [0.0, 0.0, 0.0]))
-        .collect::<(Vec<[f32; 3]>, Vec<[f32; 3]>)>();
-
-    let mut normals_count = vec![0; normals.len()];
-    for face in &stl.faces {
-        for vi in face.vertices {
-            normals[vi][0] += face.normal[0];
-            normals[vi][1] += face.normal[1];
-            normals[vi][2] += face.normal[2];
-            normals_count[vi] += 1;
+        .map(|face| stl_io::IndexedTriangle {
+            normal: face.normal,
+            vertices: face.vertices.map(|vi| remap[vi]),
+        })
+        .collect();
+
+    IndexedMesh { vertices, faces }
+}
+
+/// For every face, the interior angle at each of its three corners:
+/// `theta = acos(dot(normalize(e1), normalize(e2)))` where `e1`/`e2` are the
+/// vectors from that corner to the triangle's other two vertices.
+fn corner_angles(stl: &IndexedMesh) -> Vec<[f32; 3]> {
+    stl.faces
+        .iter()
+        .map(|face| {
+            let v = face.vertices.map(|vi| {
+                let p = stl.vertices[vi];
+                [p[0], p[1], p[2]]
+            });
+            let mut angles = [0.0; 3];
+            for corner in 0..3 {
+                let e1 = normalize3(sub3(v[(corner + 1) % 3], v[corner]));
+                let e2 = normalize3(sub3(v[(corner + 2) % 3], v[corner]));
+                angles[corner] = dot3(e1, e2).clamp(-1.0, 1.0).acos();
+            }
+            angles
+        })
+        .collect()
+}
+
+/// Clusters a vertex's incident `(face_index, corner_index)` pairs into
+/// smooth-shading groups. With no crease angle every incident face lands in
+/// one group (fully smooth); otherwise a face joins the first group whose
+/// faces are all within `crease_angle` radians of its normal, else starts a
+/// new group, so hard edges stay sharp instead of being smoothed over.
+fn group_by_crease(
+    corners: &[(usize, usize)],
+    stl: &IndexedMesh,
+    crease_angle: Option<f32>,
+) -> Vec<Vec<(usize, usize)>> {
+    let Some(threshold) = crease_angle else {
+        return vec![corners.to_vec()];
+    };
+
+    let face_normal = |face_idx: usize| {
+        let n = stl.faces[face_idx].normal;
+        normalize3([n[0], n[1], n[2]])
+    };
+
+    let mut groups: Vec<Vec<(usize, usize)>> = Vec::new();
+    for &(face_idx, corner) in corners {
+        let normal = face_normal(face_idx);
+        let group = groups.iter_mut().find(|group| {
+            group.iter().all(|&(other_face, _)| {
+                dot3(normal, face_normal(other_face))
+                    .clamp(-1.0, 1.0)
+                    .acos()
+                    <= threshold
+            })
+        });
+        match group {
+            Some(group) => group.push((face_idx, corner)),
+            None => groups.push(vec![(face_idx, corner)]),
+        }
+    }
+    groups
+}
+
+/// Builds deduplicated `positions`/`normals`/`indices` buffers from an
+/// indexed STL, angle-weighting each vertex's incident face normals (each
+/// face normal weighted by the interior angle it subtends at that vertex,
+/// rather than averaged unweighted) so slivers don't dominate the shading.
+///
+/// When `crease_angle` (radians) is `Some`, vertices where adjacent faces'
+/// normals diverge by more than the threshold are split into separate
+/// vertices so hard edges stay sharp; `None` smooths every incident face
+/// together, matching the previous behavior's topology (one vertex in, one
+/// vertex out).
+fn compute_shaded_mesh(
+    stl: &IndexedMesh,
+    crease_angle: Option<f32>,
+) -> (Vec<[f32; 3]>, Vec<[f32; 3]>, Vec<u32>) {
+    let mut incident: Vec<Vec<(usize, usize)>> = vec![Vec::new(); stl.vertices.len()];
+    for (face_idx, face) in stl.faces.iter().enumerate() {
+        for (corner, &vi) in face.vertices.iter().enumerate() {
+            incident[vi].push((face_idx, corner));
         }
     }
 
-    // normalize
-    for i in 0..normals.len() {
-        let n = normals[i];
-        let count = normals_count[i] as f32;
-        normals[i] = [n[0] / count, n[1] / count, n[2] / count];
+    let angles = corner_angles(stl);
+
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut remap = vec![[0u32; 3]; stl.faces.len()];
+
+    for (vi, corners) in incident.into_iter().enumerate() {
+        for group in group_by_crease(&corners, stl, crease_angle) {
+            let mut normal = [0.0; 3];
+            for &(face_idx, corner) in &group {
+                let face_normal = stl.faces[face_idx].normal;
+                let weight = angles[face_idx][corner];
+                normal = add3(
+                    normal,
+                    scale3([face_normal[0], face_normal[1], face_normal[2]], weight),
+                );
+            }
+            normal = normalize3(normal);
+
+            let new_index = positions.len() as u32;
+            let p = stl.vertices[vi];
+            positions.push([p[0], p[1], p[2]]);
+            normals.push(normal);
+
+            for &(face_idx, corner) in &group {
+                remap[face_idx][corner] = new_index;
+            }
+        }
     }
 
+    let indices = remap.into_iter().flatten().collect();
+    (positions, normals, indices)
+}
+
+/// Knobs shared by every STL -> glTF/OBJ export path. `weld_epsilon`, when
+/// set, welds coincident vertices (see [`weld_vertices`]) before anything
+/// else runs, so it also shrinks the input to normal smoothing.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConvertOptions {
+    pub color: Option<PbrMaterial>,
+    pub crease_angle: Option<f32>,
+    pub layout: VertexLayout,
+    pub weld_epsilon: Option<f32>,
+}
+
+/// Pushes a single STL mesh's buffers/accessors/primitive into `gltf`,
+/// returning the resulting mesh index. Shared by the single-mesh and
+/// multi-mesh (batch/assembly) entry points.
+fn push_stl_mesh(
+    gltf: &mut GltfBuilder,
+    name: &str,
+    stl: IndexedMesh,
+    options: &ConvertOptions,
+) -> json::Index<json::Mesh> {
+    let stl = match options.weld_epsilon {
+        Some(epsilon) => weld_vertices(&stl, epsilon),
+        None => stl,
+    };
+    let (positions, normals, indices) = compute_shaded_mesh(&stl, options.crease_angle);
+
     let (min, max) = bounding_coords(&positions);
-    println!("min: {min:?} max: {max:?}");
     let vcount = positions.len();
 
-    let positions_view =
-        gltf.push_buffer_with_view(Some("positions".to_string()), positions, None, None);
+    let (positions, normals) = match options.layout {
+        VertexLayout::Planar => {
+            let positions_view = gltf.push_buffer_with_view(
+                Some(format!("{name}_positions")),
+                positions,
+                None,
+                None,
+            );
+            let normals_view =
+                gltf.push_buffer_with_view(Some(format!("{name}_normals")), normals, None, None);
 
-    let normals_view = gltf.push_buffer_with_view(Some("normals".to_string()), normals, None, None);
+            let positions = gltf.push_accessor_vec3(
+                Some(format!("{name}_positions")),
+                positions_view,
+                0,
+                vcount,
+                Some(min),
+                Some(max),
+            );
+            let normals = gltf.push_accessor_vec3(
+                Some(format!("{name}_normals")),
+                normals_view,
+                3,
+                vcount,
+                None,
+                None,
+            );
+            (positions, normals)
+        }
+        VertexLayout::Interleaved => {
+            let view =
+                gltf.push_interleaved_view(Some(format!("{name}_vertices")), positions, normals);
 
-    let positions = gltf.push_accessor_vec3(
-        Some("positions".to_string()),
-        positions_view,
-        0,
-        vcount,
-        Some(min),
-        Some(max),
-    );
-    let normals = gltf.push_accessor_vec3(
-        Some("normals".to_string()),
-        normals_view,
-        3,
-        vcount,
-        None,
+            let positions = gltf.push_accessor_vec3(
+                Some(format!("{name}_positions")),
+                view,
+                0,
+                vcount,
+                Some(min),
+                Some(max),
+            );
+            let normals = gltf.push_accessor_vec3(
+                Some(format!("{name}_normals")),
+                view,
+                12,
+                vcount,
+                None,
+                None,
+            );
+            (positions, normals)
+        }
+    };
+
+    let nb_indices = indices.len();
+    let indices_view = gltf.push_buffer_with_view(
+        Some(format!("{name}_indices")),
+        indices,
+        Some(json::buffer::Target::ElementArrayBuffer),
         None,
     );
+    let indices =
+        Some(gltf.push_accessor_u32(Some(format!("{name}_indices")), indices_view, 0, nb_indices));
 
-    let indices = stl
-        .faces
-        .iter()
-        .flat_map(|it| {
-            [
-                it.vertices[0] as u32,
-                it.vertices[1] as u32,
-                it.vertices[2] as u32,
-            ]
-        })
-        .collect::<Vec<_>>();
-    let nb_indices = indices.len();
-    let indices_view =
-        gltf.push_buffer_with_view(Some("indices".to_string()), indices, Some(1), None);
-    let indices = if with_indices {
-        Some(gltf.push_accessor_u32(Some("indices".to_string()), indices_view, 0, nb_indices))
-    } else {
-        None
-    };
+    let material = options
+        .color
+        .map(|color| gltf.push_material(Some(format!("{name}_material")), color));
 
     let primitive = json::mesh::Primitive {
         attributes: {
@@ -130,12 +479,63 @@ pub fn convert_stl_to_gltf(
         extensions: Default::default(),
         extras: Default::default(),
         indices,
-        material: None,
+        material,
         mode: Valid(json::mesh::Mode::Triangles),
         targets: None,
     };
 
-    let mesh = gltf.push_mesh(Some(mesh_name.clone()), vec![primitive], None);
+    gltf.push_mesh(Some(name.to_string()), vec![primitive], None)
+}
+
+pub fn convert_stl_to_gltf(
+    stl: IndexedMesh,
+    input_filename: impl AsRef<Path>,
+) -> Result<GltfBuilder, String> {
+    convert_stl_to_gltf_with_color(stl, input_filename, None)
+}
+
+/// Like [`convert_stl_to_gltf`], but also assigns a solid base color to the
+/// converted mesh instead of leaving it untextured.
+pub fn convert_stl_to_gltf_with_color(
+    stl: IndexedMesh,
+    input_filename: impl AsRef<Path>,
+    color: Option<PbrMaterial>,
+) -> Result<GltfBuilder, String> {
+    convert_stl_to_gltf_with_options(
+        stl,
+        input_filename,
+        &ConvertOptions {
+            color,
+            ..Default::default()
+        },
+    )
+}
+
+/// Like [`convert_stl_to_gltf_with_color`], but takes the full set of
+/// [`ConvertOptions`]: crease-angle threshold, vertex buffer layout, and
+/// vertex-welding epsilon.
+pub fn convert_stl_to_gltf_with_options(
+    stl: IndexedMesh,
+    input_filename: impl AsRef<Path>,
+    options: &ConvertOptions,
+) -> Result<GltfBuilder, String> {
+    let mesh_name = input_filename
+        .as_ref()
+        .file_stem()
+        .unwrap()
+        .to_string_lossy()
+        .to_string();
+
+    let (min, max) = bounding_coords(
+        &stl.vertices
+            .iter()
+            .map(|v| [v[0], v[1], v[2]])
+            .collect::<Vec<_>>(),
+    );
+    println!("min: {min:?} max: {max:?}");
+
+    let mut gltf = GltfBuilder::new();
+    let mesh = push_stl_mesh(&mut gltf, &mesh_name, stl, options);
     let node = gltf.push_node(Some(mesh_name), Some(mesh), None, None);
     let scene = gltf.push_scene(vec![node]);
     gltf.set_default_scene(Some(scene));
@@ -143,6 +543,88 @@ pub fn convert_stl_to_gltf(
     Ok(gltf)
 }
 
+/// A named mesh plus its placement within a scene, as passed to
+/// [`convert_meshes_to_gltf`].
+pub struct SceneMesh {
+    pub name: String,
+    pub stl: IndexedMesh,
+    pub transform: NodeTrs,
+    pub options: ConvertOptions,
+}
+
+/// Converts several named, independently-placed meshes (e.g. multiple STL
+/// files, or the solids of a multi-solid source) into a single glTF scene
+/// graph: one node per mesh, each carrying its own TRS, optionally parented
+/// under a shared root node rather than collapsed into one mesh.
+pub fn convert_meshes_to_gltf(
+    meshes: Vec<SceneMesh>,
+    root_name: Option<String>,
+) -> Result<GltfBuilder, String> {
+    let mut gltf = GltfBuilder::new();
+
+    let child_nodes = meshes
+        .into_iter()
+        .map(|scene_mesh| {
+            let mesh = push_stl_mesh(
+                &mut gltf,
+                &scene_mesh.name,
+                scene_mesh.stl,
+                &scene_mesh.options,
+            );
+            gltf.push_node_trs(
+                Some(scene_mesh.name),
+                Some(mesh),
+                scene_mesh.transform,
+                None,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let scene_nodes = if let Some(root_name) = root_name {
+        vec![gltf.push_node_trs(Some(root_name), None, NodeTrs::default(), Some(child_nodes))]
+    } else {
+        child_nodes
+    };
+
+    let scene = gltf.push_scene(scene_nodes);
+    gltf.set_default_scene(Some(scene));
+
+    Ok(gltf)
+}
+
+/// Converts an indexed STL mesh into an `ObjDocument` ready to be written
+/// out as `.obj` (+ optional companion `.mtl`).
+pub fn convert_stl_to_obj(stl: IndexedMesh) -> Result<ObjDocument, String> {
+    convert_stl_to_obj_with_options(stl, None, None, None)
+}
+
+/// Like [`convert_stl_to_obj`], but also takes a solid diffuse color (written
+/// out as the companion `.mtl`'s `Kd`), a vertex-welding epsilon (see
+/// [`weld_vertices`]), and a crease-angle threshold for sharp-edge normal
+/// splitting.
+pub fn convert_stl_to_obj_with_options(
+    stl: IndexedMesh,
+    color: Option<[f32; 3]>,
+    weld_epsilon: Option<f32>,
+    crease_angle: Option<f32>,
+) -> Result<ObjDocument, String> {
+    let stl = match weld_epsilon {
+        Some(epsilon) => weld_vertices(&stl, epsilon),
+        None => stl,
+    };
+    let (positions, normals, indices) = compute_shaded_mesh(&stl, crease_angle);
+
+    Ok(ObjDocument {
+        positions,
+        normals,
+        indices,
+        material: color.map(|diffuse_color| ObjMaterial {
+            name: "material".to_string(),
+            diffuse_color,
+        }),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use std::{
@@ -152,6 +634,7 @@ mod tests {
     };
 
     use crate::convert_stl_to_gltf;
+    use crate::IndexedMesh;
 
     #[test]
     fn test_glb() {
@@ -212,4 +695,253 @@ mod tests {
             println!("Output: {}", outpath.display());
         }
     }
+
+    fn right_triangle_mesh(normal: [f32; 3]) -> IndexedMesh {
+        IndexedMesh {
+            vertices: vec![
+                stl_io::Vertex::new([0.0, 0.0, 0.0]),
+                stl_io::Vertex::new([1.0, 0.0, 0.0]),
+                stl_io::Vertex::new([0.0, 1.0, 0.0]),
+            ],
+            faces: vec![stl_io::IndexedTriangle {
+                normal: stl_io::Normal::new(normal),
+                vertices: [0, 1, 2],
+            }],
+        }
+    }
+
+    #[test]
+    fn corner_angles_of_right_triangle() {
+        use crate::corner_angles;
+        use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
+
+        let stl = right_triangle_mesh([0.0, 0.0, 1.0]);
+        let angles = corner_angles(&stl);
+
+        assert_eq!(angles.len(), 1);
+        let [right_angle, a, b] = angles[0];
+        assert!((right_angle - FRAC_PI_2).abs() < 1e-5);
+        assert!((a - FRAC_PI_4).abs() < 1e-5);
+        assert!((b - FRAC_PI_4).abs() < 1e-5);
+    }
+
+    #[test]
+    fn group_by_crease_splits_faces_past_the_threshold() {
+        use crate::group_by_crease;
+        use std::f32::consts::FRAC_PI_4;
+
+        // Two faces sharing a vertex, normals 90 degrees apart.
+        let stl = IndexedMesh {
+            vertices: right_triangle_mesh([0.0, 0.0, 1.0]).vertices,
+            faces: vec![
+                stl_io::IndexedTriangle {
+                    normal: stl_io::Normal::new([0.0, 0.0, 1.0]),
+                    vertices: [0, 1, 2],
+                },
+                stl_io::IndexedTriangle {
+                    normal: stl_io::Normal::new([1.0, 0.0, 0.0]),
+                    vertices: [0, 1, 2],
+                },
+            ],
+        };
+        let corners = [(0, 0), (1, 0)];
+
+        let split = group_by_crease(&corners, &stl, Some(FRAC_PI_4));
+        assert_eq!(split.len(), 2);
+
+        let smoothed = group_by_crease(&corners, &stl, None);
+        assert_eq!(smoothed.len(), 1);
+        assert_eq!(smoothed[0].len(), 2);
+    }
+
+    #[test]
+    fn compute_shaded_mesh_normalizes_single_face_normal() {
+        use crate::compute_shaded_mesh;
+
+        let stl = right_triangle_mesh([0.0, 0.0, 2.0]);
+        let (positions, normals, indices) = compute_shaded_mesh(&stl, None);
+
+        assert_eq!(positions.len(), 3);
+        assert_eq!(indices, vec![0, 1, 2]);
+        for n in &normals {
+            assert!((n[2] - 1.0).abs() < 1e-5);
+            let len = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+            assert!((len - 1.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn weld_vertices_merges_coincident_points() {
+        use crate::weld_vertices;
+
+        let stl = IndexedMesh {
+            vertices: vec![
+                stl_io::Vertex::new([0.0, 0.0, 0.0]),
+                stl_io::Vertex::new([1.0, 0.0, 0.0]),
+                stl_io::Vertex::new([0.0, 1.0, 0.0]),
+                stl_io::Vertex::new([0.0, 0.0, 0.0]), // duplicate of vertex 0
+            ],
+            faces: vec![
+                stl_io::IndexedTriangle {
+                    normal: stl_io::Normal::new([0.0, 0.0, 1.0]),
+                    vertices: [0, 1, 2],
+                },
+                stl_io::IndexedTriangle {
+                    normal: stl_io::Normal::new([0.0, 0.0, 1.0]),
+                    vertices: [3, 2, 1],
+                },
+            ],
+        };
+
+        let welded = weld_vertices(&stl, 1e-4);
+
+        assert_eq!(welded.vertices.len(), 3);
+        assert_eq!(welded.faces[1].vertices[0], welded.faces[0].vertices[0]);
+    }
+
+    #[test]
+    fn gltf_roundtrip_preserves_vertex_and_face_counts() {
+        use crate::convert_gltf_to_stl;
+
+        let stl = right_triangle_mesh([0.0, 0.0, 1.0]);
+        let gltf = convert_stl_to_gltf(stl, "roundtrip.stl").unwrap();
+        let glb = gltf.to_glb().unwrap();
+
+        let path = std::env::temp_dir().join("conv3d_gltf_roundtrip_test.glb");
+        let file = File::create(&path).unwrap();
+        glb.to_writer(BufWriter::new(file)).unwrap();
+
+        let roundtripped = convert_gltf_to_stl(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(roundtripped.vertices.len(), 3);
+        assert_eq!(roundtripped.faces.len(), 1);
+        assert_eq!(roundtripped.faces[0].vertices, [0, 1, 2]);
+    }
+
+    #[test]
+    fn obj_writer_emits_geometry_and_material_lines() {
+        use crate::convert_stl_to_obj_with_options;
+
+        let stl = right_triangle_mesh([0.0, 0.0, 1.0]);
+        let doc = convert_stl_to_obj_with_options(stl, Some([1.0, 0.0, 0.0]), None, None).unwrap();
+
+        let mut obj = Vec::new();
+        doc.write_obj(&mut obj, Some("mesh.mtl")).unwrap();
+        let obj = String::from_utf8(obj).unwrap();
+
+        assert!(obj.contains("mtllib mesh.mtl"));
+        assert_eq!(obj.lines().filter(|l| l.starts_with("v ")).count(), 3);
+        assert_eq!(obj.lines().filter(|l| l.starts_with("vn ")).count(), 3);
+        assert!(obj.contains("usemtl material"));
+        assert!(obj.contains("f 1//1 2//2 3//3"));
+
+        let mut mtl = Vec::new();
+        doc.write_mtl(&mut mtl).unwrap();
+        let mtl = String::from_utf8(mtl).unwrap();
+
+        assert!(mtl.contains("newmtl material"));
+        assert!(mtl.contains("Kd 1 0 0"));
+    }
+
+    #[test]
+    fn convert_meshes_to_gltf_places_each_mesh_under_its_own_node() {
+        use crate::{convert_meshes_to_gltf, ConvertOptions, NodeTrs, SceneMesh};
+
+        let meshes = vec![
+            SceneMesh {
+                name: "a".to_string(),
+                stl: right_triangle_mesh([0.0, 0.0, 1.0]),
+                transform: NodeTrs {
+                    translation: Some([1.0, 0.0, 0.0]),
+                    ..Default::default()
+                },
+                options: ConvertOptions::default(),
+            },
+            SceneMesh {
+                name: "b".to_string(),
+                stl: right_triangle_mesh([0.0, 0.0, 1.0]),
+                transform: NodeTrs {
+                    translation: Some([2.0, 0.0, 0.0]),
+                    ..Default::default()
+                },
+                options: ConvertOptions::default(),
+            },
+        ];
+
+        let gltf = convert_meshes_to_gltf(meshes, Some("root".to_string())).unwrap();
+        let root = gltf.root();
+
+        // One node per mesh plus the shared root node.
+        assert_eq!(root.nodes.len(), 3);
+
+        let root_node = root
+            .nodes
+            .iter()
+            .find(|node| node.name.as_deref() == Some("root"))
+            .unwrap();
+        assert_eq!(root_node.children.as_ref().map(Vec::len), Some(2));
+
+        let a = root
+            .nodes
+            .iter()
+            .find(|node| node.name.as_deref() == Some("a"))
+            .unwrap();
+        assert_eq!(a.translation, Some([1.0, 0.0, 0.0]));
+        let b = root
+            .nodes
+            .iter()
+            .find(|node| node.name.as_deref() == Some("b"))
+            .unwrap();
+        assert_eq!(b.translation, Some([2.0, 0.0, 0.0]));
+    }
+
+    #[test]
+    fn convert_stl_to_gltf_with_color_attaches_material_to_the_primitive() {
+        use crate::{convert_stl_to_gltf_with_color, PbrMaterial};
+
+        let stl = right_triangle_mesh([0.0, 0.0, 1.0]);
+        let color = PbrMaterial::solid_color([1.0, 0.0, 0.0]);
+        let gltf = convert_stl_to_gltf_with_color(stl, "colored.stl", Some(color)).unwrap();
+        let root = gltf.root();
+
+        assert_eq!(root.materials.len(), 1);
+        assert_eq!(
+            root.materials[0].pbr_metallic_roughness.base_color_factor.0,
+            [1.0, 0.0, 0.0, 1.0]
+        );
+
+        let material_index = root.meshes[0].primitives[0].material.unwrap();
+        assert_eq!(material_index.value(), 0);
+    }
+
+    #[test]
+    fn interleaved_layout_shares_one_buffer_view_with_a_24_byte_stride() {
+        use crate::{convert_stl_to_gltf_with_options, json, ConvertOptions, Valid, VertexLayout};
+
+        let stl = right_triangle_mesh([0.0, 0.0, 1.0]);
+        let gltf = convert_stl_to_gltf_with_options(
+            stl,
+            "interleaved.stl",
+            &ConvertOptions {
+                layout: VertexLayout::Interleaved,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let root = gltf.root();
+
+        let primitive = &root.meshes[0].primitives[0];
+        let positions =
+            &root.accessors[primitive.attributes[&Valid(json::mesh::Semantic::Positions)].value()];
+        let normals =
+            &root.accessors[primitive.attributes[&Valid(json::mesh::Semantic::Normals)].value()];
+
+        assert_eq!(positions.buffer_view, normals.buffer_view);
+        assert_eq!(positions.byte_offset.map(|o| o.0), Some(0));
+        assert_eq!(normals.byte_offset.map(|o| o.0), Some(12));
+
+        let view = &root.buffer_views[positions.buffer_view.unwrap().value()];
+        assert_eq!(view.byte_stride.map(|s| s.0), Some(24));
+    }
 }
@@ -0,0 +1,82 @@
+use std::io::{self, Write};
+
+/// A material written into the companion `.mtl` file: a name and a diffuse
+/// color. The default is a flat, non-shiny gray.
+#[derive(Debug, Clone)]
+pub struct ObjMaterial {
+    pub name: String,
+    pub diffuse_color: [f32; 3],
+}
+
+impl Default for ObjMaterial {
+    fn default() -> Self {
+        ObjMaterial {
+            name: "default".to_string(),
+            diffuse_color: [0.8, 0.8, 0.8],
+        }
+    }
+}
+
+/// Deduplicated vertex/normal/index buffers for a single mesh, ready to be
+/// serialized as Wavefront OBJ. Mirrors the buffers `convert_stl_to_gltf`
+/// builds, so both exporters shade the same way.
+pub struct ObjDocument {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    pub indices: Vec<u32>,
+    pub material: Option<ObjMaterial>,
+}
+
+impl ObjDocument {
+    /// Writes `v`/`vn`/`f v//vn` lines. `mtllib` is the filename of a
+    /// companion `.mtl` to reference, if one was written.
+    pub fn write_obj<W: Write>(&self, mut writer: W, mtllib: Option<&str>) -> Result<(), String> {
+        (|| -> io::Result<()> {
+            if let Some(mtllib) = mtllib {
+                writeln!(writer, "mtllib {mtllib}")?;
+            }
+
+            for p in &self.positions {
+                writeln!(writer, "v {} {} {}", p[0], p[1], p[2])?;
+            }
+            for n in &self.normals {
+                writeln!(writer, "vn {} {} {}", n[0], n[1], n[2])?;
+            }
+
+            if let Some(material) = &self.material {
+                writeln!(writer, "usemtl {}", material.name)?;
+            }
+
+            for face in self.indices.chunks_exact(3) {
+                // OBJ indices are 1-based.
+                writeln!(
+                    writer,
+                    "f {a}//{a} {b}//{b} {c}//{c}",
+                    a = face[0] + 1,
+                    b = face[1] + 1,
+                    c = face[2] + 1,
+                )?;
+            }
+
+            Ok(())
+        })()
+        .map_err(|e| e.to_string())
+    }
+
+    /// Writes a minimal `.mtl` with this document's material, or with
+    /// [`ObjMaterial::default`] when it has none.
+    pub fn write_mtl<W: Write>(&self, mut writer: W) -> Result<(), String> {
+        let material = self.material.clone().unwrap_or_default();
+        (|| -> io::Result<()> {
+            writeln!(writer, "newmtl {}", material.name)?;
+            writeln!(
+                writer,
+                "Kd {} {} {}",
+                material.diffuse_color[0], material.diffuse_color[1], material.diffuse_color[2]
+            )?;
+            writeln!(writer, "illum 1")?;
+            Ok(())
+        })()
+        .map_err(|e| e.to_string())
+    }
+}